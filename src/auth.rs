@@ -0,0 +1,94 @@
+// Client-facing authentication for the proxy itself.
+//
+// This module lets the proxy be exposed as a standalone, multi-tenant
+// service rather than assuming it only ever sits behind a trusted local
+// network. Callers must present a signed JWT; the validated claims are
+// threaded through the handler layer so security assessments and metrics
+// can be attributed per-caller.
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::handlers::ApiError;
+use crate::AppState;
+
+//------------------------------------------------------------------------------
+// Claims
+//------------------------------------------------------------------------------
+
+// Claims validated from the caller's `Authorization: Bearer <jwt>` header.
+//
+// `sub` identifies the calling tenant/user and is attached to security
+// assessments and LLM metrics so multi-tenant deployments can attribute
+// usage per-caller.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthClaims {
+    pub sub: String,
+    pub aud: Option<String>,
+    pub exp: usize,
+}
+
+//------------------------------------------------------------------------------
+// Extractor
+//------------------------------------------------------------------------------
+
+// Axum extractor that validates the caller's bearer JWT before a handler
+// runs. Reject with `ApiError::Unauthorized` if the header is missing, the
+// signature doesn't verify, or `exp`/`aud` don't check out.
+//
+// # Arguments
+//
+// * `parts` - The incoming request parts, used to read the `Authorization` header
+// * `state` - Application state, used to read the configured JWT secret/audience
+//
+// # Returns
+//
+// * `Ok(AuthClaims)` - The validated claims
+// * `Err(ApiError)` - If the token is missing, malformed, or fails validation
+impl FromRequestParts<AppState> for AuthClaims {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let header = parts
+            .headers
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| ApiError::Unauthorized("Missing Authorization header".to_string()))?;
+
+        let token = header
+            .strip_prefix("Bearer ")
+            .ok_or_else(|| ApiError::Unauthorized("Expected a Bearer token".to_string()))?;
+
+        let jwt_config = &state.jwt_config;
+
+        let mut validation = Validation::new(jwt_config.algorithm);
+        validation.set_audience(&[&jwt_config.audience]);
+
+        let claims = decode::<AuthClaims>(token, &jwt_config.decoding_key, &validation)
+            .map_err(|e| {
+                debug!("Rejecting request with invalid JWT: {}", e);
+                ApiError::Unauthorized("Invalid or expired token".to_string())
+            })?
+            .claims;
+
+        Ok(claims)
+    }
+}
+
+//------------------------------------------------------------------------------
+// Configuration
+//------------------------------------------------------------------------------
+
+// Shared JWT validation settings, held in `AppState` and used by the
+// `AuthClaims` extractor on every gated request.
+#[derive(Clone)]
+pub struct JwtConfig {
+    pub algorithm: Algorithm,
+    pub decoding_key: DecodingKey,
+    pub audience: String,
+}
@@ -0,0 +1,494 @@
+// Backend abstraction for the chat and generate completion flows.
+//
+// The security-assessment flow in `handlers::chat`/`handlers::generate` was
+// originally wired directly to Ollama's `/api/chat` and `/api/generate`
+// shapes. `ChatBackend`/`GenerateBackend` let the same flows front any
+// OpenAI-compatible `/v1/chat/completions` endpoint as well. The handlers
+// use the single `Backend` held in `AppState` for every request; the
+// request's `model` field does not select between backends. Each
+// implementation is responsible for mapping the Ollama-shaped request/
+// response types that clients already send to whatever shape its upstream
+// actually speaks - including, for streaming, decoding the upstream's wire
+// format (Ollama NDJSON or OpenAI SSE) into `ChatResponse`/`GenerateResponse`
+// NDJSON before `handlers::streaming` ever sees it - so the handler layer
+// stays backend-agnostic.
+use async_stream::stream;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use reqwest::Client;
+use serde_json::json;
+use tracing::{error, warn};
+
+use crate::handlers::streaming::serialize;
+use crate::handlers::ApiError;
+use crate::ollama::OllamaClient;
+use crate::types::{ChatRequest, ChatResponse, GenerateRequest, GenerateResponse, Message};
+
+//------------------------------------------------------------------------------
+// Shared types
+//------------------------------------------------------------------------------
+
+// A normalized stream of NDJSON-encoded delta lines, already shaped like the
+// `Resp` type `handlers::streaming::handle_assessed_stream` is instantiated
+// with (`ChatResponse` for chat, `GenerateResponse` for generate). Producing
+// this is the backend's job, regardless of what wire format the upstream
+// actually speaks.
+pub type DeltaStream = BoxStream<'static, Result<Bytes, ApiError>>;
+
+//------------------------------------------------------------------------------
+// Chat trait
+//------------------------------------------------------------------------------
+
+// The result of a non-streaming chat completion: the normalized
+// `ChatResponse` used for security assessment, alongside the raw
+// upstream body so the unmodified (e.g. Ollama) path can pass every field
+// straight through to the client and into `log_llm_metrics` instead of
+// round-tripping through `ChatResponse`'s 4 known fields and dropping the
+// rest.
+pub struct ChatCompletion {
+    pub response: ChatResponse,
+    pub raw: Bytes,
+}
+
+// A chat completion provider the proxy can sit in front of.
+//
+// Implementations translate the client-facing, Ollama-shaped request/response
+// into whatever the upstream actually expects and back, so
+// `handlers::chat` only ever deals in `ChatRequest`/`ChatResponse`.
+#[async_trait]
+pub trait ChatBackend: Send + Sync {
+    // Performs a single non-streaming chat completion, returning the
+    // response normalized back to the Ollama-shaped `ChatResponse` plus the
+    // raw upstream bytes.
+    async fn chat_completions(&self, request: &ChatRequest) -> Result<ChatCompletion, ApiError>;
+
+    // Forwards a streaming chat completion, returning a `ChatResponse`-NDJSON
+    // delta stream already normalized from whatever the upstream speaks on
+    // the wire.
+    async fn chat_completions_streaming(&self, request: &ChatRequest)
+        -> Result<DeltaStream, ApiError>;
+}
+
+//------------------------------------------------------------------------------
+// Generate trait
+//------------------------------------------------------------------------------
+
+// The result of a non-streaming generate completion, mirroring
+// `ChatCompletion`.
+pub struct GenerateCompletion {
+    pub response: GenerateResponse,
+    pub raw: Bytes,
+}
+
+// A text-generation provider the proxy can sit in front of, mirroring
+// `ChatBackend` for `/api/generate`.
+#[async_trait]
+pub trait GenerateBackend: Send + Sync {
+    // Performs a single non-streaming generate completion, returning the
+    // response normalized back to the Ollama-shaped `GenerateResponse` plus
+    // the raw upstream bytes.
+    async fn generate(&self, request: &GenerateRequest) -> Result<GenerateCompletion, ApiError>;
+
+    // Forwards a streaming generate completion, returning a
+    // `GenerateResponse`-NDJSON delta stream already normalized from
+    // whatever the upstream speaks on the wire.
+    async fn generate_streaming(
+        &self,
+        request: &GenerateRequest,
+    ) -> Result<DeltaStream, ApiError>;
+}
+
+//------------------------------------------------------------------------------
+// Combined backend
+//------------------------------------------------------------------------------
+
+// A single configured provider fronting both chat and generate, so
+// selecting a backend (Ollama vs. an OpenAI-compatible upstream) governs
+// both endpoints together instead of drifting independently.
+pub trait Backend: ChatBackend + GenerateBackend {}
+
+impl<T: ChatBackend + GenerateBackend> Backend for T {}
+
+//------------------------------------------------------------------------------
+// Ollama backend
+//------------------------------------------------------------------------------
+
+// Default backend: forwards requests as-is to an Ollama (or
+// Ollama-compatible) upstream, since `ChatRequest`/`ChatResponse` already
+// mirror Ollama's `/api/chat` shape.
+pub struct OllamaBackend {
+    client: OllamaClient,
+}
+
+impl OllamaBackend {
+    pub fn new(client: OllamaClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ChatBackend for OllamaBackend {
+    async fn chat_completions(&self, request: &ChatRequest) -> Result<ChatCompletion, ApiError> {
+        let response = self.client.forward("/api/chat", request).await?;
+        let raw = response.bytes().await.map_err(|e| {
+            error!("Failed to read response body from Ollama: {}", e);
+            ApiError::InternalError("Failed to read response body".to_string())
+        })?;
+
+        let response = serde_json::from_slice(&raw).map_err(|e| {
+            error!("Failed to parse Ollama response: {}", e);
+            ApiError::InternalError("Failed to parse response".to_string())
+        })?;
+
+        Ok(ChatCompletion { response, raw })
+    }
+
+    async fn chat_completions_streaming(
+        &self,
+        request: &ChatRequest,
+    ) -> Result<DeltaStream, ApiError> {
+        let response = self.client.forward("/api/chat", request).await?;
+        Ok(ollama_delta_stream(response))
+    }
+}
+
+#[async_trait]
+impl GenerateBackend for OllamaBackend {
+    async fn generate(&self, request: &GenerateRequest) -> Result<GenerateCompletion, ApiError> {
+        let response = self.client.forward("/api/generate", request).await?;
+        let raw = response.bytes().await.map_err(|e| {
+            error!("Failed to read response body from Ollama: {}", e);
+            ApiError::InternalError("Failed to read response body".to_string())
+        })?;
+
+        let response = serde_json::from_slice(&raw).map_err(|e| {
+            error!("Failed to parse Ollama response: {}", e);
+            ApiError::InternalError("Failed to parse response".to_string())
+        })?;
+
+        Ok(GenerateCompletion { response, raw })
+    }
+
+    async fn generate_streaming(
+        &self,
+        request: &GenerateRequest,
+    ) -> Result<DeltaStream, ApiError> {
+        let response = self.client.forward("/api/generate", request).await?;
+        Ok(ollama_delta_stream(response))
+    }
+}
+
+// Ollama's NDJSON wire format already matches the `ChatResponse`/
+// `GenerateResponse` shape `handlers::streaming` expects, so there's
+// nothing to decode here - just re-wrap the upstream byte stream's error
+// type to match `DeltaStream`.
+fn ollama_delta_stream(response: reqwest::Response) -> DeltaStream {
+    Box::pin(response.bytes_stream().map(|chunk| {
+        chunk.map_err(|e| {
+            error!("Streaming upstream read failed: {}", e);
+            ApiError::InternalError("Streaming upstream read failed".to_string())
+        })
+    }))
+}
+
+//------------------------------------------------------------------------------
+// OpenAI-compatible backend
+//------------------------------------------------------------------------------
+
+// Adapter for any `/v1/chat/completions`-compatible upstream (OpenAI,
+// vLLM, TGI, etc). Maps the Ollama-shaped `ChatRequest`/`GenerateRequest` to
+// an OpenAI-shaped body and normalizes the OpenAI-shaped response back to
+// `ChatResponse`/`GenerateResponse`, decoding SSE into NDJSON for the
+// streaming paths.
+pub struct OpenAiBackend {
+    http: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiBackend {
+    pub fn new(base_url: String, api_key: Option<String>) -> Self {
+        Self {
+            http: Client::new(),
+            base_url,
+            api_key,
+        }
+    }
+
+    fn to_openai_body(request: &ChatRequest) -> serde_json::Value {
+        json!({
+            "model": request.model,
+            "messages": request.messages,
+            "stream": request.stream.unwrap_or(false),
+        })
+    }
+
+    fn from_openai_body(
+        model: &str,
+        body: serde_json::Value,
+    ) -> Result<ChatResponse, ApiError> {
+        let content = body["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or_else(|| {
+                ApiError::InternalError("Unexpected OpenAI-compatible response shape".to_string())
+            })?
+            .to_string();
+
+        Ok(ChatResponse {
+            model: model.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            message: Message {
+                role: "assistant".to_string(),
+                content,
+            },
+            done: true,
+        })
+    }
+
+    // `/api/generate`'s prompt has no OpenAI-compatible equivalent, so it's
+    // sent as a single user message - the same mapping an OpenAI-compatible
+    // server's own completion-style front ends typically use.
+    fn to_openai_generate_body(request: &GenerateRequest) -> serde_json::Value {
+        json!({
+            "model": request.model,
+            "messages": [{ "role": "user", "content": request.prompt }],
+            "stream": request.stream.unwrap_or(false),
+        })
+    }
+
+    fn from_openai_body_to_generate(
+        model: &str,
+        body: serde_json::Value,
+    ) -> Result<GenerateResponse, ApiError> {
+        let content = body["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or_else(|| {
+                ApiError::InternalError("Unexpected OpenAI-compatible response shape".to_string())
+            })?
+            .to_string();
+
+        Ok(GenerateResponse {
+            model: model.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            response: content,
+            context: None,
+            done: true,
+        })
+    }
+
+    // Opens a streaming OpenAI-compatible completion and decodes its SSE
+    // body (`data: {...}` lines, blank-line separated, terminated by
+    // `data: [DONE]`) into `(delta_text, is_done)` pairs - the one piece
+    // both `chat_completions_streaming` and `generate_streaming` share,
+    // since they differ only in which `Resp` type they wrap each pair in.
+    async fn open_ai_sse_deltas(
+        &self,
+        url: String,
+        body: serde_json::Value,
+    ) -> Result<BoxStream<'static, Result<(String, bool), ApiError>>, ApiError> {
+        let mut req = self.http.post(&url).json(&body);
+        if let Some(key) = &self.api_key {
+            req = req.bearer_auth(key);
+        }
+
+        let response = req.send().await.map_err(|e| {
+            error!(
+                "Failed to reach OpenAI-compatible backend at {} for streaming: {}",
+                url, e
+            );
+            ApiError::InternalError("Failed to reach upstream backend".to_string())
+        })?;
+
+        let mut upstream = response.bytes_stream();
+
+        let deltas = stream! {
+            let mut carry = Vec::<u8>::new();
+
+            'sse: while let Some(chunk) = upstream.next().await {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        error!("OpenAI-compatible streaming read failed: {}", e);
+                        yield Err(ApiError::InternalError(
+                            "OpenAI-compatible streaming read failed".to_string(),
+                        ));
+                        break 'sse;
+                    }
+                };
+
+                carry.extend_from_slice(&chunk);
+
+                while let Some(newline_at) = carry.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = carry.drain(..=newline_at).collect();
+                    let line = line.strip_suffix(b"\n").unwrap_or(&line);
+                    let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+                    let Some(payload) = line.strip_prefix(b"data:") else {
+                        continue;
+                    };
+                    let payload = payload.strip_prefix(b" ").unwrap_or(payload);
+                    if payload.is_empty() {
+                        continue;
+                    }
+                    if payload == b"[DONE]" {
+                        break 'sse;
+                    }
+
+                    let Ok(event) = serde_json::from_slice::<serde_json::Value>(payload) else {
+                        warn!("Skipping malformed OpenAI-compatible SSE chunk");
+                        continue;
+                    };
+
+                    let delta_text = event["choices"][0]["delta"]["content"]
+                        .as_str()
+                        .unwrap_or("")
+                        .to_string();
+                    let is_done = event["choices"][0]["finish_reason"].is_string();
+
+                    yield Ok((delta_text, is_done));
+
+                    if is_done {
+                        break 'sse;
+                    }
+                }
+            }
+        };
+
+        Ok(Box::pin(deltas))
+    }
+}
+
+#[async_trait]
+impl ChatBackend for OpenAiBackend {
+    async fn chat_completions(&self, request: &ChatRequest) -> Result<ChatCompletion, ApiError> {
+        let url = format!("{}/v1/chat/completions", self.base_url);
+        let mut req = self.http.post(&url).json(&Self::to_openai_body(request));
+        if let Some(key) = &self.api_key {
+            req = req.bearer_auth(key);
+        }
+
+        let response = req.send().await.map_err(|e| {
+            error!("Failed to reach OpenAI-compatible backend at {}: {}", url, e);
+            ApiError::InternalError("Failed to reach upstream backend".to_string())
+        })?;
+
+        let body: serde_json::Value = response.json().await.map_err(|e| {
+            error!("Failed to parse OpenAI-compatible response: {}", e);
+            ApiError::InternalError("Failed to parse response".to_string())
+        })?;
+
+        let response = Self::from_openai_body(&request.model, body)?;
+        // There's no original Ollama-shaped body to pass through for this
+        // backend, so the normalized response is the closest thing to raw
+        // bytes available.
+        let raw = Bytes::from(serde_json::to_vec(&response).unwrap_or_default());
+
+        Ok(ChatCompletion { response, raw })
+    }
+
+    async fn chat_completions_streaming(
+        &self,
+        request: &ChatRequest,
+    ) -> Result<DeltaStream, ApiError> {
+        let url = format!("{}/v1/chat/completions", self.base_url);
+        let mut body = Self::to_openai_body(request);
+        body["stream"] = json!(true);
+
+        let model = request.model.clone();
+        let mut deltas = self.open_ai_sse_deltas(url, body).await?;
+
+        let stream = stream! {
+            while let Some(item) = deltas.next().await {
+                match item {
+                    Ok((text, done)) => {
+                        let chat_delta = ChatResponse {
+                            model: model.clone(),
+                            created_at: chrono::Utc::now().to_rfc3339(),
+                            message: Message {
+                                role: "assistant".to_string(),
+                                content: text,
+                            },
+                            done,
+                        };
+                        yield Ok(Bytes::from(serialize(&chat_delta)));
+                    }
+                    Err(e) => {
+                        yield Err(e);
+                        break;
+                    }
+                }
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+}
+
+#[async_trait]
+impl GenerateBackend for OpenAiBackend {
+    async fn generate(&self, request: &GenerateRequest) -> Result<GenerateCompletion, ApiError> {
+        let url = format!("{}/v1/chat/completions", self.base_url);
+        let mut req = self
+            .http
+            .post(&url)
+            .json(&Self::to_openai_generate_body(request));
+        if let Some(key) = &self.api_key {
+            req = req.bearer_auth(key);
+        }
+
+        let response = req.send().await.map_err(|e| {
+            error!("Failed to reach OpenAI-compatible backend at {}: {}", url, e);
+            ApiError::InternalError("Failed to reach upstream backend".to_string())
+        })?;
+
+        let body: serde_json::Value = response.json().await.map_err(|e| {
+            error!("Failed to parse OpenAI-compatible response: {}", e);
+            ApiError::InternalError("Failed to parse response".to_string())
+        })?;
+
+        let response = Self::from_openai_body_to_generate(&request.model, body)?;
+        // There's no original Ollama-shaped body to pass through for this
+        // backend, so the normalized response is the closest thing to raw
+        // bytes available.
+        let raw = Bytes::from(serde_json::to_vec(&response).unwrap_or_default());
+
+        Ok(GenerateCompletion { response, raw })
+    }
+
+    async fn generate_streaming(
+        &self,
+        request: &GenerateRequest,
+    ) -> Result<DeltaStream, ApiError> {
+        let url = format!("{}/v1/chat/completions", self.base_url);
+        let mut body = Self::to_openai_generate_body(request);
+        body["stream"] = json!(true);
+
+        let model = request.model.clone();
+        let mut deltas = self.open_ai_sse_deltas(url, body).await?;
+
+        let stream = stream! {
+            while let Some(item) = deltas.next().await {
+                match item {
+                    Ok((text, done)) => {
+                        let generate_delta = GenerateResponse {
+                            model: model.clone(),
+                            created_at: chrono::Utc::now().to_rfc3339(),
+                            response: text,
+                            context: None,
+                            done,
+                        };
+                        yield Ok(Bytes::from(serialize(&generate_delta)));
+                    }
+                    Err(e) => {
+                        yield Err(e);
+                        break;
+                    }
+                }
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+}
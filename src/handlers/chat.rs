@@ -16,16 +16,37 @@
 // - Consistent error handling and security violation reporting
 // - Transparent proxying of valid requests to Ollama backend
 use axum::{extract::State, response::Response, Json};
+use futures::stream::{self, StreamExt};
 use tracing::{debug, error, info};
 
+use crate::auth::AuthClaims;
+use crate::handlers::streaming::{self, StreamingDelta};
 use crate::handlers::utils::{
-    build_json_response, build_violation_response, format_security_violation_message,
-    handle_streaming_request, log_llm_metrics,
+    assessment_concurrency, build_json_response, build_violation_response,
+    format_security_violation_message, log_llm_metrics,
 };
 use crate::handlers::ApiError;
 use crate::types::{ChatRequest, ChatResponse, Message};
 use crate::AppState;
 
+impl StreamingDelta for ChatResponse {
+    fn delta_text(&self) -> &str {
+        &self.message.content
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.message.content = text;
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn set_done(&mut self, done: bool) {
+        self.done = done;
+    }
+}
+
 //------------------------------------------------------------------------------
 // Public API
 //------------------------------------------------------------------------------
@@ -33,14 +54,16 @@ use crate::AppState;
 // Handles chat completion requests with security assessment.
 //
 // This handler:
-// 1. Performs security checks on incoming chat messages
-// 2. Routes the request to Ollama if messages pass security checks
-// 3. Scans the response for security issues before returning to client
-// 4. Handles both streaming and non-streaming responses
+// 1. Requires a valid client-facing JWT, identifying the calling tenant
+// 2. Performs security checks on incoming chat messages
+// 3. Routes the request to the configured backend if messages pass security checks
+// 4. Scans the response for security issues before returning to client
+// 5. Handles both streaming and non-streaming responses
 //
 // # Arguments
 //
 // * `State(state)` - Application state containing client connections
+// * `claims` - The caller's validated JWT claims, used to attribute this request
 // * `Json(request)` - The chat completion request from the client
 //
 // # Returns
@@ -49,12 +72,16 @@ use crate::AppState;
 // * `Err(ApiError)` - If an error occurs during processing
 pub async fn handle_chat(
     State(state): State<AppState>,
+    claims: AuthClaims,
     Json(request): Json<ChatRequest>,
 ) -> Result<Response, ApiError> {
     // Ensure stream parameter is always set
     // request.stream = Some(false);
 
-    info!("Received chat request for model: {}", request.model);
+    info!(
+        "Received chat request for model: {} (caller={})",
+        request.model, claims.sub
+    );
     debug!(
         "Chat request details: stream={}, messages={}",
         request.stream.unwrap(),
@@ -62,17 +89,17 @@ pub async fn handle_chat(
     );
 
     // Security assessment: check all input messages for policy violations
-    if let Err(response) = assess_chat_messages(&state, &request).await? {
+    if let Err(response) = assess_chat_messages(&state, &request, &claims.sub).await? {
         return Ok(response);
     }
 
     // Route based on streaming or non-streaming mode
     if request.stream.unwrap() {
         debug!("Handling streaming chat request");
-        handle_streaming_chat(State(state), Json(request)).await
+        handle_streaming_chat(State(state), claims, Json(request)).await
     } else {
         debug!("Handling non-streaming chat request");
-        handle_non_streaming_chat(State(state), Json(request)).await
+        handle_non_streaming_chat(State(state), claims, Json(request)).await
     }
 }
 
@@ -80,15 +107,19 @@ pub async fn handle_chat(
 // Helper Functions
 //------------------------------------------------------------------------------
 
-// Assesses all chat messages for security policy violations.
+// Assesses all chat messages for security policy violations concurrently.
 //
-// Iterates through each message in the chat request and uses the security client
-// to check for policy violations or harmful content.
+// Fires `assess_content` for every message up to a bounded concurrency cap
+// (see `assessment_concurrency`) instead of awaiting them one at a time, and
+// short-circuits as soon as the first unsafe verdict comes back rather than
+// waiting for every in-flight assessment to finish. Message order is
+// preserved in logs even though assessments complete out of order.
 //
 // # Arguments
 //
 // * `state` - Application state containing security client
 // * `request` - The chat request containing messages to assess
+// * `caller` - The tenant/user id these assessments are attributed to
 //
 // # Returns
 //
@@ -98,21 +129,34 @@ pub async fn handle_chat(
 async fn assess_chat_messages(
     state: &AppState,
     request: &ChatRequest,
+    caller: &str,
 ) -> Result<Result<(), Response>, ApiError> {
-    for (index, message) in request.messages.iter().enumerate() {
-        debug!(
-            "Assessing message {}/{}: role={}",
-            index + 1,
-            request.messages.len(),
-            message.role
-        );
-
-        let assessment = state
-            .security_client
-            .assess_content(&message.content, &request.model, true)
-            .await?;
+    let total = request.messages.len();
+
+    let mut assessments = stream::iter(request.messages.iter().enumerate())
+        .map(|(index, message)| async move {
+            debug!(
+                "Assessing message {}/{}: role={} (caller={})",
+                index + 1,
+                total,
+                message.role,
+                caller
+            );
+
+            let assessment = state
+                .security_client
+                .assess_content(&message.content, &request.model, true, caller)
+                .await?;
+
+            Ok::<_, ApiError>((index, assessment))
+        })
+        .buffer_unordered(assessment_concurrency());
+
+    while let Some(result) = assessments.next().await {
+        let (index, assessment) = result?;
 
         if !assessment.is_safe {
+            debug!("Message {}/{} failed security assessment", index + 1, total);
             let blocked_message = format_security_violation_message(&assessment);
 
             let response = ChatResponse {
@@ -142,6 +186,7 @@ async fn assess_chat_messages(
 // # Arguments
 //
 // * `State(state)` - Application state containing client connections
+// * `claims` - The caller's validated JWT claims, used to attribute this request
 // * `Json(request)` - The chat completion request from the client
 //
 // # Returns
@@ -150,32 +195,33 @@ async fn assess_chat_messages(
 // * `Err(ApiError)` - If an error occurs during processing
 async fn handle_non_streaming_chat(
     State(state): State<AppState>,
+    claims: AuthClaims,
     Json(request): Json<ChatRequest>,
 ) -> Result<Response, ApiError> {
-    // Forward request to Ollama
-    let response = state.ollama_client.forward("/api/chat", &request).await?;
-    let body_bytes = response.bytes().await.map_err(|e| {
-        error!("Failed to read response body: {}", e);
-        ApiError::InternalError("Failed to read response body".to_string())
-    })?;
-
-    // Parse response
-    let mut response_body: ChatResponse = serde_json::from_slice(&body_bytes).map_err(|e| {
-        error!("Failed to parse response: {}", e);
-        ApiError::InternalError("Failed to parse response".to_string())
-    })?;
-
-    debug!("Received response from Ollama, performing security assessment");
-
-    // Extract and log performance metrics if available
+    // Forward request through the configured chat backend (Ollama or an
+    // OpenAI-compatible provider)
+    let completion = state.backend.chat_completions(&request).await?;
+    let mut response_body = completion.response;
+    let body_bytes = completion.raw;
+
+    debug!("Received response from backend, performing security assessment");
+
+    // Extract and log performance metrics from the raw upstream body (so
+    // fields `ChatResponse` doesn't model, like `eval_count`/`*_duration`,
+    // still get logged), attributed to the calling tenant
     if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
-        log_llm_metrics(&json, false);
+        log_llm_metrics(&json, &claims.sub, false);
     }
 
     // Security assessment on response content
     let assessment = state
         .security_client
-        .assess_content(&response_body.message.content, &request.model, false)
+        .assess_content(
+            &response_body.message.content,
+            &request.model,
+            false,
+            &claims.sub,
+        )
         .await?;
 
     if !assessment.is_safe {
@@ -185,8 +231,23 @@ async fn handle_non_streaming_chat(
         return build_violation_response(response_body);
     }
 
+    // If the response was allowed but PANW provided masked content, use it;
+    // otherwise pass the raw upstream bytes through unmodified so fields
+    // `ChatResponse` doesn't model reach the client intact.
+    if assessment.is_masked {
+        debug!("Using masked content for chat response");
+        response_body.message.content = assessment.final_content;
+
+        let json_bytes = serde_json::to_vec(&response_body).map_err(|e| {
+            error!("Failed to serialize masked chat response: {}", e);
+            ApiError::InternalError("Failed to serialize response".to_string())
+        })?;
+
+        return build_json_response(json_bytes.into());
+    }
+
     info!("Chat response passed security checks, returning to client");
-    Ok(build_json_response(body_bytes)?)
+    build_json_response(body_bytes)
 }
 
 // Handles streaming chat requests using the generic streaming handler.
@@ -197,6 +258,7 @@ async fn handle_non_streaming_chat(
 // # Arguments
 //
 // * `State(state)` - Application state containing client connections
+// * `claims` - The caller's validated JWT claims, used to attribute this request
 // * `Json(request)` - The chat completion request from the client
 //
 // # Returns
@@ -205,18 +267,18 @@ async fn handle_non_streaming_chat(
 // * `Err(ApiError)` - If an error occurs during processing
 async fn handle_streaming_chat(
     State(state): State<AppState>,
+    claims: AuthClaims,
     Json(request): Json<ChatRequest>,
 ) -> Result<Response, ApiError> {
-    debug!("Processing streaming chat request");
+    debug!(
+        "Processing streaming chat request for caller={}",
+        claims.sub
+    );
 
     let model = request.model.clone();
-    // For streaming chat, we're dealing with responses from the LLM, so is_prompt should be false
-    handle_streaming_request::<ChatRequest, ChatResponse>(
-        &state,
-        request,
-        "/api/chat",
-        &model,
-        false,
-    )
-    .await
+    // Route through the configured chat backend (Ollama or an
+    // OpenAI-compatible provider), same as the non-streaming path.
+    let upstream = state.backend.chat_completions_streaming(&request).await?;
+
+    streaming::handle_assessed_stream::<ChatResponse>(state, upstream, model, claims.sub).await
 }
@@ -0,0 +1,164 @@
+// Embeddings request handler for the Ollama API proxy.
+//
+// Mirrors the security posture of `handlers::generate` for Ollama's
+// `/api/embeddings` endpoint: text handed to the embedding model is scanned
+// with `assess_content` before being forwarded, so prompts feeding a vector
+// store get the same protection as chat and generate prompts.
+//
+// `EmbeddingsRequest`/`EmbeddingsResponse` live here rather than
+// `crate::types` for now; they belong alongside `ChatRequest`/
+// `GenerateRequest` once this proxy's type module picks them up.
+use axum::{extract::State, response::Response, Json};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info};
+
+use crate::auth::AuthClaims;
+use crate::handlers::utils::{
+    assessment_concurrency, build_json_response, build_violation_response,
+    format_security_violation_message,
+};
+use crate::handlers::ApiError;
+use crate::AppState;
+
+//------------------------------------------------------------------------------
+// Types
+//------------------------------------------------------------------------------
+
+// An embeddings request accepts either a single string or a batch of
+// strings, matching Ollama's `/api/embeddings` input shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingsInput {
+    Single(String),
+    Batch(Vec<String>),
+}
+
+impl EmbeddingsInput {
+    fn texts(&self) -> Vec<&str> {
+        match self {
+            EmbeddingsInput::Single(text) => vec![text.as_str()],
+            EmbeddingsInput::Batch(texts) => texts.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsRequest {
+    pub model: String,
+    pub input: EmbeddingsInput,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct EmbeddingsResponse {
+    pub embeddings: Vec<Vec<f32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+//------------------------------------------------------------------------------
+// Public API
+//------------------------------------------------------------------------------
+
+// Handles embeddings requests with security assessment.
+//
+// This handler:
+// 1. Requires a valid client-facing JWT, identifying the calling tenant
+// 2. Performs security checks on every input text (single or batch)
+// 3. Routes the request to Ollama if all inputs pass security checks
+// 4. Blocks the whole batch with a violation response if any input fails
+//
+// # Arguments
+//
+// * `State(state)` - Application state containing client connections
+// * `claims` - The caller's validated JWT claims, used to attribute this request
+// * `Json(request)` - The embeddings request from the client
+//
+// # Returns
+//
+// * `Ok(Response)` - The embeddings response
+// * `Err(ApiError)` - If an error occurs during processing
+pub async fn handle_embeddings(
+    State(state): State<AppState>,
+    claims: AuthClaims,
+    Json(request): Json<EmbeddingsRequest>,
+) -> Result<Response, ApiError> {
+    let texts = request.input.texts();
+    info!(
+        "Received embeddings request for model: {} ({} input(s), caller={})",
+        request.model,
+        texts.len(),
+        claims.sub
+    );
+
+    if let Err(response) =
+        assess_embeddings_input(&state, &request.model, &texts, &claims.sub).await?
+    {
+        return Ok(response);
+    }
+
+    debug!("Embeddings input passed security checks, forwarding to Ollama");
+
+    let response = state
+        .ollama_client
+        .forward("/api/embeddings", &request)
+        .await?;
+    let body_bytes = response.bytes().await.map_err(|e| {
+        error!("Failed to read embeddings response body: {}", e);
+        ApiError::InternalError("Failed to read response body".to_string())
+    })?;
+
+    build_json_response(body_bytes)
+}
+
+//------------------------------------------------------------------------------
+// Helper Functions
+//------------------------------------------------------------------------------
+
+// Assesses every input text for security policy violations, concurrently
+// and with an early exit on the first unsafe verdict, mirroring
+// `handlers::chat::assess_chat_messages`.
+//
+// # Arguments
+//
+// * `state` - Application state containing security client
+// * `model` - The embedding model name, passed through to `assess_content`
+// * `texts` - The input text(s) to assess
+// * `caller` - The tenant/user id these assessments are attributed to
+//
+// # Returns
+//
+// * `Ok(Ok(()))` - If all inputs pass security checks
+// * `Ok(Err(Response))` - If security violation is detected, with appropriate response
+// * `Err(ApiError)` - If an error occurs during security assessment
+async fn assess_embeddings_input(
+    state: &AppState,
+    model: &str,
+    texts: &[&str],
+    caller: &str,
+) -> Result<Result<(), Response>, ApiError> {
+    let mut assessments = stream::iter(texts.iter().enumerate())
+        .map(|(index, text)| async move {
+            debug!("Assessing embeddings input {}/{}", index + 1, texts.len());
+            state
+                .security_client
+                .assess_content(text, model, true, caller)
+                .await
+        })
+        .buffer_unordered(assessment_concurrency());
+
+    while let Some(assessment) = assessments.next().await {
+        let assessment = assessment?;
+
+        if !assessment.is_safe {
+            let response = EmbeddingsResponse {
+                embeddings: Vec::new(),
+                error: Some(format_security_violation_message(&assessment)),
+            };
+
+            return Ok(Err(build_violation_response(response)?));
+        }
+    }
+
+    Ok(Ok(()))
+}
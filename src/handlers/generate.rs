@@ -5,14 +5,34 @@
 use axum::{extract::State, response::Response, Json};
 use tracing::{debug, error};
 
+use crate::auth::AuthClaims;
+use crate::handlers::streaming::{self, StreamingDelta};
 use crate::handlers::utils::{
     build_json_response, build_violation_response, format_security_violation_message,
-    handle_streaming_request, log_llm_metrics,
+    log_llm_metrics,
 };
 use crate::handlers::ApiError;
 use crate::types::{GenerateRequest, GenerateResponse};
 use crate::AppState;
 
+impl StreamingDelta for GenerateResponse {
+    fn delta_text(&self) -> &str {
+        &self.response
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.response = text;
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn set_done(&mut self, done: bool) {
+        self.done = done;
+    }
+}
+
 // Handles text generation requests with security assessment.
 //
 // This handler:
@@ -24,6 +44,7 @@ use crate::AppState;
 // # Arguments
 //
 // * `State(state)` - Application state containing client connections
+// * `claims` - The caller's validated JWT claims, used to attribute this request
 // * `Json(request)` - The generation request from the client
 //
 // # Returns
@@ -32,25 +53,29 @@ use crate::AppState;
 // * `Err(ApiError)` - If an error occurs during processing
 pub async fn handle_generate(
     State(state): State<AppState>,
+    claims: AuthClaims,
     Json(request): Json<GenerateRequest>,
 ) -> Result<Response, ApiError> {
     // Ensure stream parameter is explicitly set
     // request.stream = Some(false);
 
-    debug!("Received generate request for model: {}", request.model);
+    debug!(
+        "Received generate request for model: {} (caller={})",
+        request.model, claims.sub
+    );
 
     // Check the input prompt for security violations
-    if let Err(response) = assess_generate_prompt(&state, &request).await? {
+    if let Err(response) = assess_generate_prompt(&state, &request, &claims.sub).await? {
         return Ok(response);
     }
 
     // Route based on streaming or non-streaming mode
     if request.stream.unwrap() {
         debug!("Handling streaming generate request");
-        handle_streaming_generate(State(state), Json(request)).await
+        handle_streaming_generate(State(state), claims, Json(request)).await
     } else {
         debug!("Handling non-streaming generate request");
-        handle_non_streaming_generate(State(state), Json(request)).await
+        handle_non_streaming_generate(State(state), claims, Json(request)).await
     }
 }
 
@@ -60,6 +85,7 @@ pub async fn handle_generate(
 //
 // * `state` - Application state containing security client
 // * `request` - The generation request containing the prompt to assess
+// * `caller` - The tenant/user id this assessment is attributed to
 //
 // # Returns
 //
@@ -69,11 +95,12 @@ pub async fn handle_generate(
 async fn assess_generate_prompt(
     state: &AppState,
     request: &GenerateRequest,
+    caller: &str,
 ) -> Result<Result<(), Response>, ApiError> {
     // Check input prompt
     let assessment = state
         .security_client
-        .assess_content(&request.prompt, &request.model, true)
+        .assess_content(&request.prompt, &request.model, true, caller)
         .await?;
 
     // If the content is not safe, create a blocked response
@@ -99,6 +126,7 @@ async fn assess_generate_prompt(
 // # Arguments
 //
 // * `State(state)` - Application state containing client connections
+// * `claims` - The caller's validated JWT claims, used to attribute this request
 // * `Json(request)` - The generation request from the client
 //
 // # Returns
@@ -107,37 +135,31 @@ async fn assess_generate_prompt(
 // * `Err(ApiError)` - If an error occurs during processing
 async fn handle_non_streaming_generate(
     State(state): State<AppState>,
+    claims: AuthClaims,
     Json(request): Json<GenerateRequest>,
 ) -> Result<Response, ApiError> {
-    debug!("Processing non-streaming generate request");
-
-    // Forward request to Ollama
-    let response = state
-        .ollama_client
-        .forward("/api/generate", &request)
-        .await?;
-
-    // Read response body
-    let body_bytes = response.bytes().await.map_err(|e| {
-        error!("Failed to read response body: {}", e);
-        ApiError::InternalError("Failed to read response body".to_string())
-    })?;
-
-    // Extract and log performance metrics if available
+    debug!(
+        "Processing non-streaming generate request for caller={}",
+        claims.sub
+    );
+
+    // Forward request through the configured generate backend (Ollama or
+    // an OpenAI-compatible provider)
+    let completion = state.backend.generate(&request).await?;
+    let mut response_body = completion.response;
+    let body_bytes = completion.raw;
+
+    // Extract and log performance metrics from the raw upstream body (so
+    // fields `GenerateResponse` doesn't model, like `eval_count`/
+    // `*_duration`, still get logged), attributed to the calling tenant
     if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
-        log_llm_metrics(&json, false);
+        log_llm_metrics(&json, &claims.sub, false);
     }
 
-    // Parse response
-    let mut response_body: GenerateResponse = serde_json::from_slice(&body_bytes).map_err(|e| {
-        error!("Failed to parse response: {}", e);
-        ApiError::InternalError("Failed to parse response".to_string())
-    })?;
-
     // Check model output for security issues
     let assessment = state
         .security_client
-        .assess_content(&response_body.response, &request.model, false)
+        .assess_content(&response_body.response, &request.model, false, &claims.sub)
         .await?;
 
     // If response is not safe, replace content with security message
@@ -162,7 +184,9 @@ async fn handle_non_streaming_generate(
         return build_json_response(json_bytes.into());
     }
 
-    // Return original (safe) response
+    // Response was allowed and not masked: pass the raw upstream bytes
+    // through unmodified so fields `GenerateResponse` doesn't model reach
+    // the client intact.
     build_json_response(body_bytes)
 }
 
@@ -171,6 +195,7 @@ async fn handle_non_streaming_generate(
 // # Arguments
 //
 // * `State(state)` - Application state containing client connections
+// * `claims` - The caller's validated JWT claims, used to attribute this request
 // * `Json(request)` - The generation request from the client
 //
 // # Returns
@@ -179,18 +204,18 @@ async fn handle_non_streaming_generate(
 // * `Err(ApiError)` - If an error occurs during processing
 async fn handle_streaming_generate(
     State(state): State<AppState>,
+    claims: AuthClaims,
     Json(request): Json<GenerateRequest>,
 ) -> Result<Response, ApiError> {
-    debug!("Setting up streaming generate request");
+    debug!(
+        "Setting up streaming generate request for caller={}",
+        claims.sub
+    );
 
     let model = request.model.clone();
-    // For streaming generate, we're dealing with responses from the LLM, so is_prompt should be false
-    handle_streaming_request::<GenerateRequest>(
-        &state,
-        request,
-        "/api/generate",
-        &model,
-        false,
-    )
-    .await
+    // Route through the configured generate backend (Ollama or an
+    // OpenAI-compatible provider), same as the non-streaming path.
+    let upstream = state.backend.generate_streaming(&request).await?;
+
+    streaming::handle_assessed_stream::<GenerateResponse>(state, upstream, model, claims.sub).await
 }
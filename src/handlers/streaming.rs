@@ -0,0 +1,374 @@
+// Buffered, security-assessed streaming for chat and generate.
+//
+// Scanning every individual SSE token would both spam the PANW AI Runtime
+// API and miss violations that straddle token boundaries. Instead this
+// module accumulates decoded deltas into a rolling text buffer, flushes the
+// buffer to `assess_content` on sentence/newline boundaries (or once it
+// exceeds `FLUSH_THRESHOLD_CHARS`), and forwards the flushed segment to the
+// client only once it's been cleared. If a flush comes back unsafe, the
+// upstream stream is torn down and a final security-violation chunk is
+// emitted instead of the remaining tokens. When PANW instead returns masked
+// content for a safe flush, the masked text is forwarded in place of the
+// buffered segment so redactions apply to streaming the same way they do
+// for non-streaming responses. A clean final delta, a violation, a failed
+// assessment, or the upstream closing while delta text is still buffered
+// each end the stream with exactly one terminal chunk with `done: true`.
+// If the upstream closes with no text buffered and no final delta seen, no
+// terminal chunk is added; trailing bytes that don't parse as a delta are
+// discarded.
+use async_stream::stream;
+use axum::body::Body;
+use axum::response::Response;
+use bytes::Bytes;
+use futures::StreamExt;
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{debug, error, warn};
+
+use crate::backend::DeltaStream;
+use crate::handlers::utils::format_security_violation_message;
+use crate::handlers::ApiError;
+use crate::AppState;
+
+//------------------------------------------------------------------------------
+// Configuration
+//------------------------------------------------------------------------------
+
+// Buffer is flushed once it reaches this many characters, even without a
+// sentence/newline boundary, so a single unbroken line of tokens can't
+// delay assessment indefinitely.
+const FLUSH_THRESHOLD_CHARS: usize = 200;
+
+//------------------------------------------------------------------------------
+// Delta access
+//------------------------------------------------------------------------------
+
+// Lets this module read and rewrite the streamed text of either
+// `ChatResponse` or `GenerateResponse` without duplicating the buffering
+// loop for each.
+pub trait StreamingDelta: DeserializeOwned + Serialize {
+    fn delta_text(&self) -> &str;
+    fn set_text(&mut self, text: String);
+    fn is_done(&self) -> bool;
+    fn set_done(&mut self, done: bool);
+}
+
+//------------------------------------------------------------------------------
+// Buffering helpers
+//------------------------------------------------------------------------------
+
+// Pops one newline-terminated line off the front of `carry`, stripping the
+// trailing newline, or returns `None` if `carry` doesn't contain a complete
+// line yet (e.g. an upstream chunk boundary landed mid-line, and the rest
+// is still to come). Pulled out of the main loop so the chunk-boundary
+// handling can be unit tested without a live upstream connection.
+fn drain_line(carry: &mut Vec<u8>) -> Option<Vec<u8>> {
+    let newline_at = carry.iter().position(|b| *b == b'\n')?;
+    let mut line: Vec<u8> = carry.drain(..=newline_at).collect();
+    line.pop();
+    Some(line)
+}
+
+// Whether a buffered delta segment is due for assessment: it's the final
+// delta, the buffer has reached `FLUSH_THRESHOLD_CHARS`, or it ends on a
+// sentence/newline boundary.
+fn should_flush(text_buffer: &str, is_final: bool) -> bool {
+    is_final
+        || text_buffer.chars().count() >= FLUSH_THRESHOLD_CHARS
+        || text_buffer.ends_with('\n')
+        || text_buffer.trim_end().ends_with(['.', '!', '?'])
+}
+
+//------------------------------------------------------------------------------
+// Public API
+//------------------------------------------------------------------------------
+
+// Relays an already-normalized upstream delta stream back to the client,
+// assessing buffered text before each segment is forwarded. Both chat and
+// generate go through `state.backend` to obtain this stream, so whatever
+// provider is configured (Ollama-native NDJSON, or an adapted
+// OpenAI-compatible SSE body) has already been normalized to
+// `Resp`-shaped NDJSON lines by the time it reaches this function - it
+// only deals in the resulting bytes.
+//
+// # Arguments
+//
+// * `state` - Application state containing the security client
+// * `upstream` - The already-normalized upstream delta stream to relay
+// * `model` - The model name, passed through to `assess_content`
+// * `caller` - The tenant/user id these assessments are attributed to
+//
+// # Returns
+//
+// * `Ok(Response)` - An NDJSON streaming response, assessed segment by segment
+// * `Err(ApiError)` - If the response fails to build
+pub async fn handle_assessed_stream<Resp>(
+    state: AppState,
+    upstream: DeltaStream,
+    model: String,
+    caller: String,
+) -> Result<Response, ApiError>
+where
+    Resp: StreamingDelta + Send + 'static,
+{
+    let mut upstream_lines = upstream;
+
+    let body = stream! {
+        let mut carry = Vec::<u8>::new();
+        let mut text_buffer = String::new();
+        // Tracks the most recently parsed delta so a forced end-of-stream
+        // flush (below) has a concrete chunk shape to attach leftover text
+        // to and mark done. Set whenever a line parses, taken whenever that
+        // delta is used to yield a chunk.
+        let mut last_delta: Option<Resp> = None;
+        // Set once a clean terminal chunk (final, violation, or error) has
+        // already been yielded, so the end-of-stream drain below doesn't
+        // double-terminate the response.
+        let mut terminal_sent = false;
+
+        'upstream: while let Some(chunk) = upstream_lines.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(_) => {
+                    // The backend already logged the underlying cause at the
+                    // point the upstream read or SSE decode failed.
+                    error!("Streaming upstream read failed");
+                    break 'upstream;
+                }
+            };
+
+            carry.extend_from_slice(&chunk);
+
+            while let Some(line) = drain_line(&mut carry) {
+                if line.is_empty() {
+                    continue;
+                }
+
+                let Ok(delta) = serde_json::from_slice::<Resp>(&line) else {
+                    warn!("Skipping malformed streaming chunk from upstream");
+                    continue;
+                };
+
+                text_buffer.push_str(delta.delta_text());
+                let is_final = delta.is_done();
+                last_delta = Some(delta);
+
+                if !should_flush(&text_buffer, is_final) {
+                    continue;
+                }
+
+                let delta = last_delta.take().expect("just set above");
+                let flushed = std::mem::take(&mut text_buffer);
+                if flushed.is_empty() {
+                    if is_final {
+                        yield Ok::<_, std::io::Error>(Bytes::from(serialize(&delta)));
+                        terminal_sent = true;
+                        break 'upstream;
+                    }
+                    continue;
+                }
+
+                match state
+                    .security_client
+                    .assess_content(&flushed, &model, false, &caller)
+                    .await
+                {
+                    Ok(assessment) if !assessment.is_safe => {
+                        debug!("Buffered streaming segment failed security assessment");
+                        let mut violation = delta;
+                        violation.set_text(format_security_violation_message(&assessment));
+                        violation.set_done(true);
+                        yield Ok(Bytes::from(serialize(&violation)));
+                        terminal_sent = true;
+                        break 'upstream;
+                    }
+                    Ok(assessment) => {
+                        let mut safe_delta = delta;
+                        if assessment.is_masked {
+                            debug!("Forwarding masked content for streaming segment");
+                            safe_delta.set_text(assessment.final_content);
+                        } else {
+                            safe_delta.set_text(flushed);
+                        }
+                        yield Ok(Bytes::from(serialize(&safe_delta)));
+                        if is_final {
+                            terminal_sent = true;
+                            break 'upstream;
+                        }
+                    }
+                    Err(e) => {
+                        error!("Security assessment failed mid-stream: {}", e);
+                        let mut error_delta = delta;
+                        error_delta.set_text(
+                            "Stream terminated: security assessment failed".to_string(),
+                        );
+                        error_delta.set_done(true);
+                        yield Ok(Bytes::from(serialize(&error_delta)));
+                        terminal_sent = true;
+                        break 'upstream;
+                    }
+                }
+            }
+        }
+
+        // The upstream byte stream ended (or failed) without a clean
+        // terminal chunk above. Drain whatever's left instead of silently
+        // dropping it: a final unterminated line in `carry` (the TCP
+        // boundary landed before its trailing newline) and any text still
+        // sitting in `text_buffer`, assessed the same way as a mid-stream
+        // flush, forced to `done: true` so the client sees a proper
+        // terminal event rather than a truncated body.
+        if !terminal_sent {
+            if !carry.is_empty() {
+                if let Ok(delta) = serde_json::from_slice::<Resp>(&carry) {
+                    text_buffer.push_str(delta.delta_text());
+                    last_delta = Some(delta);
+                } else {
+                    warn!("Discarding unterminated trailing bytes from upstream");
+                }
+            }
+
+            if !text_buffer.is_empty() {
+                if let Some(mut delta) = last_delta.take() {
+                    let flushed = std::mem::take(&mut text_buffer);
+                    match state
+                        .security_client
+                        .assess_content(&flushed, &model, false, &caller)
+                        .await
+                    {
+                        Ok(assessment) if !assessment.is_safe => {
+                            debug!("Trailing streaming segment failed security assessment");
+                            delta.set_text(format_security_violation_message(&assessment));
+                        }
+                        Ok(assessment) if assessment.is_masked => {
+                            debug!("Forwarding masked content for trailing streaming segment");
+                            delta.set_text(assessment.final_content);
+                        }
+                        Ok(_) => {
+                            delta.set_text(flushed);
+                        }
+                        Err(e) => {
+                            error!(
+                                "Security assessment failed draining trailing stream segment: {}",
+                                e
+                            );
+                            delta.set_text(
+                                "Stream terminated: security assessment failed".to_string(),
+                            );
+                        }
+                    }
+                    delta.set_done(true);
+                    yield Ok::<_, std::io::Error>(Bytes::from(serialize(&delta)));
+                } else {
+                    warn!("Discarding unflushed streaming text with no delta to attach it to");
+                }
+            }
+        }
+    };
+
+    Response::builder()
+        .header("content-type", "application/x-ndjson")
+        .body(Body::from_stream(body))
+        .map_err(|e| {
+            error!("Failed to build streaming response: {}", e);
+            ApiError::InternalError("Failed to build streaming response".to_string())
+        })
+}
+
+pub(crate) fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut bytes = serde_json::to_vec(value).unwrap_or_default();
+    bytes.push(b'\n');
+    bytes
+}
+
+// These cover the buffering/flush-threshold helpers in isolation, including
+// a byte stream split across a chunk boundary mid-line. The security- and
+// masking-dependent branches inside `handle_assessed_stream` itself aren't
+// covered here since exercising them needs a live (or mocked)
+// `SecurityClient`, which this crate has no test double for yet.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct FakeDelta {
+        text: String,
+        done: bool,
+    }
+
+    impl StreamingDelta for FakeDelta {
+        fn delta_text(&self) -> &str {
+            &self.text
+        }
+
+        fn set_text(&mut self, text: String) {
+            self.text = text;
+        }
+
+        fn is_done(&self) -> bool {
+            self.done
+        }
+
+        fn set_done(&mut self, done: bool) {
+            self.done = done;
+        }
+    }
+
+    #[test]
+    fn drain_line_waits_for_a_line_split_across_chunk_boundaries() {
+        let mut carry = Vec::new();
+        carry.extend_from_slice(br#"{"text":"hel"#);
+        assert_eq!(drain_line(&mut carry), None);
+
+        carry.extend_from_slice(br#"lo","done":false}"#);
+        assert_eq!(drain_line(&mut carry), None);
+
+        carry.extend_from_slice(b"\n");
+        let line = drain_line(&mut carry).expect("line is complete now");
+        let delta: FakeDelta = serde_json::from_slice(&line).unwrap();
+        assert_eq!(delta, FakeDelta { text: "hello".to_string(), done: false });
+        assert_eq!(drain_line(&mut carry), None);
+    }
+
+    #[test]
+    fn drain_line_leaves_a_trailing_partial_line_in_carry() {
+        let mut carry = Vec::new();
+        carry.extend_from_slice(b"{\"text\":\"a\",\"done\":false}\n{\"text\":\"b\"");
+
+        let line = drain_line(&mut carry).unwrap();
+        assert_eq!(line, b"{\"text\":\"a\",\"done\":false}");
+        assert_eq!(drain_line(&mut carry), None);
+        assert_eq!(carry, b"{\"text\":\"b\"");
+    }
+
+    #[test]
+    fn drain_line_returns_none_on_empty_carry() {
+        let mut carry = Vec::new();
+        assert_eq!(drain_line(&mut carry), None);
+    }
+
+    #[test]
+    fn should_flush_once_threshold_reached() {
+        let long = "a".repeat(FLUSH_THRESHOLD_CHARS);
+        assert!(should_flush(&long, false));
+    }
+
+    #[test]
+    fn should_flush_on_sentence_boundary() {
+        assert!(should_flush("a short sentence.", false));
+        assert!(should_flush("a question?", false));
+        assert!(should_flush("an exclamation!", false));
+        assert!(should_flush("a line\n", false));
+    }
+
+    #[test]
+    fn should_not_flush_short_mid_sentence_text() {
+        assert!(!should_flush("still typing", false));
+    }
+
+    #[test]
+    fn should_flush_when_final_regardless_of_length_or_punctuation() {
+        assert!(should_flush("x", true));
+        assert!(should_flush("", true));
+    }
+}
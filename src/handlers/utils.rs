@@ -0,0 +1,76 @@
+// Shared helpers used by the chat, generate, and embeddings handlers.
+use axum::body::Bytes;
+use std::env;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use tracing::info;
+
+use crate::handlers::ApiError;
+use crate::security::Assessment;
+
+// Default number of assessments fired concurrently when
+// `SECURITY_ASSESSMENT_CONCURRENCY` isn't set.
+const DEFAULT_ASSESSMENT_CONCURRENCY: usize = 8;
+
+// Reads the configured concurrency cap for batches of `assess_content`
+// calls (chat messages, embeddings inputs, ...), falling back to
+// `DEFAULT_ASSESSMENT_CONCURRENCY` so a single large request can't
+// overwhelm the security backend with unbounded parallel calls. Shared by
+// every handler that assesses multiple inputs at once, so they all honor
+// the same knob.
+pub fn assessment_concurrency() -> usize {
+    env::var("SECURITY_ASSESSMENT_CONCURRENCY")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|&value: &usize| value > 0)
+        .unwrap_or(DEFAULT_ASSESSMENT_CONCURRENCY)
+}
+
+// Builds a `200 OK` JSON response from already-serialized bytes, passing
+// an upstream or locally-serialized body straight through to the client.
+pub fn build_json_response(body: Bytes) -> Result<Response, ApiError> {
+    Ok((StatusCode::OK, [("content-type", "application/json")], body).into_response())
+}
+
+// Builds the response returned when content is blocked by a security
+// policy violation, serializing `body` (the caller's own response shape,
+// with its content replaced by a violation message) with a non-2xx status.
+pub fn build_violation_response<T: Serialize>(body: T) -> Result<Response, ApiError> {
+    let bytes = serde_json::to_vec(&body).map_err(|e| {
+        ApiError::InternalError(format!("Failed to serialize violation response: {}", e))
+    })?;
+
+    Ok((
+        StatusCode::FORBIDDEN,
+        [("content-type", "application/json")],
+        bytes,
+    )
+        .into_response())
+}
+
+// Formats a human-readable message describing why content was blocked.
+pub fn format_security_violation_message(assessment: &Assessment) -> String {
+    format!(
+        "This content was blocked by security policy: {}",
+        assessment.reason
+    )
+}
+
+// Logs LLM performance metrics extracted from the raw upstream response
+// (e.g. `eval_count`, `total_duration`), attributed to `caller` so
+// per-tenant usage can be audited.
+pub fn log_llm_metrics(body: &serde_json::Value, caller: &str, is_streaming: bool) {
+    let eval_count = body.get("eval_count").and_then(|v| v.as_u64());
+    let total_duration = body.get("total_duration").and_then(|v| v.as_u64());
+    let prompt_eval_count = body.get("prompt_eval_count").and_then(|v| v.as_u64());
+
+    info!(
+        caller,
+        streaming = is_streaming,
+        eval_count,
+        prompt_eval_count,
+        total_duration,
+        "LLM response metrics"
+    );
+}
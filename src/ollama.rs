@@ -0,0 +1,107 @@
+// Client for forwarding requests to an upstream Ollama (or Ollama-compatible)
+// server.
+//
+// This module centralizes outbound HTTP concerns (base URL and
+// authentication) so handlers don't need to know how a request actually
+// reaches the upstream model server.
+use std::fmt;
+
+use reqwest::{Client, Response};
+use serde::Serialize;
+use tracing::error;
+
+use crate::handlers::ApiError;
+
+//------------------------------------------------------------------------------
+// Configuration
+//------------------------------------------------------------------------------
+
+// Configuration for connecting to an upstream Ollama instance.
+//
+// `bearer_token` (and the looser `api_auth` alias some deployments use for a
+// pre-formed `Authorization` header value) lets this client sit in front of
+// a secured Ollama endpoint — e.g. one fronted by a reverse proxy that
+// requires a bearer credential — instead of only plaintext localhost.
+//
+// `Debug` is implemented by hand below so logging a config never leaks
+// either secret in plaintext.
+#[derive(Clone, Default)]
+pub struct OllamaClientConfig {
+    pub base_url: String,
+    // Bearer token injected as `Authorization: Bearer <token>` on every
+    // upstream request, when set.
+    pub bearer_token: Option<String>,
+    // Pre-formatted `Authorization` header value, used instead of
+    // `bearer_token` when the upstream expects a non-Bearer scheme.
+    pub api_auth: Option<String>,
+}
+
+impl fmt::Debug for OllamaClientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OllamaClientConfig")
+            .field("base_url", &self.base_url)
+            .field("bearer_token", &self.bearer_token.as_ref().map(|_| "<redacted>"))
+            .field("api_auth", &self.api_auth.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+//------------------------------------------------------------------------------
+// Client
+//------------------------------------------------------------------------------
+
+// Thin wrapper around `reqwest::Client` that knows how to reach the
+// configured Ollama upstream and authenticate to it.
+#[derive(Clone)]
+pub struct OllamaClient {
+    http: Client,
+    config: OllamaClientConfig,
+}
+
+impl fmt::Debug for OllamaClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OllamaClient").field("config", &self.config).finish()
+    }
+}
+
+impl OllamaClient {
+    pub fn new(config: OllamaClientConfig) -> Self {
+        Self {
+            http: Client::new(),
+            config,
+        }
+    }
+
+    // Forwards `body` to `path` on the configured Ollama upstream,
+    // attaching an `Authorization` header when the client was configured
+    // with a bearer token or raw auth value.
+    //
+    // # Arguments
+    //
+    // * `path` - The upstream path, e.g. `/api/chat`
+    // * `body` - The request body to forward, serialized as JSON
+    //
+    // # Returns
+    //
+    // * `Ok(Response)` - The raw upstream response
+    // * `Err(ApiError)` - If the upstream request fails
+    pub async fn forward<T: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &T,
+    ) -> Result<Response, ApiError> {
+        let url = format!("{}{}", self.config.base_url, path);
+        let mut request = self.http.post(&url).json(body);
+
+        if let Some(auth) = &self.config.api_auth {
+            request = request.header("Authorization", auth);
+        } else if let Some(token) = &self.config.bearer_token {
+            request = request.bearer_auth(token);
+        }
+
+        request.send().await.map_err(|e| {
+            error!("Failed to forward request to Ollama at {}: {}", url, e);
+            ApiError::InternalError("Failed to reach Ollama upstream".to_string())
+        })
+    }
+}
@@ -0,0 +1,91 @@
+// Client for the Palo Alto Networks AI Runtime security assessment API.
+//
+// Scans prompts and responses for policy violations before they reach the
+// model or the caller. Every assessment is attributed to the calling
+// tenant/user so multi-tenant deployments can audit who triggered it.
+use serde::Deserialize;
+use tracing::error;
+
+use crate::handlers::ApiError;
+
+//------------------------------------------------------------------------------
+// Types
+//------------------------------------------------------------------------------
+
+// The verdict returned by the AI Runtime API for a single piece of content.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Assessment {
+    pub is_safe: bool,
+    #[serde(default)]
+    pub is_masked: bool,
+    #[serde(default)]
+    pub final_content: String,
+    #[serde(default)]
+    pub reason: String,
+}
+
+//------------------------------------------------------------------------------
+// Client
+//------------------------------------------------------------------------------
+
+#[derive(Clone)]
+pub struct SecurityClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl SecurityClient {
+    pub fn new(base_url: String, api_key: String) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url,
+            api_key,
+        }
+    }
+
+    // Assesses `content` for security policy violations.
+    //
+    // # Arguments
+    //
+    // * `content` - The text to scan
+    // * `model` - The model this content is being sent to or came from
+    // * `is_prompt` - `true` for input scanned before reaching the model, `false` for output
+    // * `caller` - The tenant/user id this assessment is attributed to
+    //
+    // # Returns
+    //
+    // * `Ok(Assessment)` - The assessment verdict
+    // * `Err(ApiError)` - If the security backend can't be reached or returns a bad response
+    pub async fn assess_content(
+        &self,
+        content: &str,
+        model: &str,
+        is_prompt: bool,
+        caller: &str,
+    ) -> Result<Assessment, ApiError> {
+        let url = format!("{}/v1/scan", self.base_url);
+
+        let response = self
+            .http
+            .post(&url)
+            .bearer_auth(&self.api_key)
+            .json(&serde_json::json!({
+                "content": content,
+                "model": model,
+                "is_prompt": is_prompt,
+                "caller": caller,
+            }))
+            .send()
+            .await
+            .map_err(|e| {
+                error!("Failed to reach security backend at {}: {}", url, e);
+                ApiError::InternalError("Failed to reach security backend".to_string())
+            })?;
+
+        response.json().await.map_err(|e| {
+            error!("Failed to parse security assessment response: {}", e);
+            ApiError::InternalError("Failed to parse security assessment".to_string())
+        })
+    }
+}